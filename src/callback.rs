@@ -0,0 +1,55 @@
+//! Support types for `syscalls::subscribe`.
+
+use crate::syscalls;
+use core::marker::PhantomData;
+
+pub trait SubscribableCallback {
+    fn call_rust(&mut self, arg1: usize, arg2: usize, arg3: usize);
+}
+
+impl<F: FnMut(usize, usize, usize)> SubscribableCallback for F {
+    fn call_rust(&mut self, arg1: usize, arg2: usize, arg3: usize) {
+        self(arg1, arg2, arg3)
+    }
+}
+
+/// A token representing a live kernel subscription. Dropping it tells the
+/// kernel to stop delivering upcalls for this `driver_number`/
+/// `subscribe_number` pair, and frees the subscription's handle in the
+/// registry so any upcall still in flight is recognized as stale rather
+/// than delivered into freed memory.
+pub struct CallbackSubscription<'a> {
+    driver_number: usize,
+    subscribe_number: usize,
+    handle: usize,
+    _lifetime: PhantomData<&'a ()>,
+}
+
+impl<'a> CallbackSubscription<'a> {
+    pub(crate) fn new(
+        driver_number: usize,
+        subscribe_number: usize,
+        handle: usize,
+    ) -> CallbackSubscription<'a> {
+        CallbackSubscription {
+            driver_number,
+            subscribe_number,
+            handle,
+            _lifetime: PhantomData,
+        }
+    }
+}
+
+impl<'a> Drop for CallbackSubscription<'a> {
+    fn drop(&mut self) {
+        extern "C" fn null_callback(_: usize, _: usize, _: usize, _: usize) {}
+
+        let _ = syscalls::subscribe_fn(
+            self.driver_number,
+            self.subscribe_number,
+            null_callback,
+            0,
+        );
+        unsafe { syscalls::handle_registry::free(self.handle) };
+    }
+}
@@ -0,0 +1,113 @@
+//! Support types for `syscalls::allow_readwrite` and `syscalls::allow_readonly`.
+
+use crate::syscalls;
+use crate::syscalls::{SuccessValues, SyscallReturn};
+
+/// A token representing a mutable buffer currently shared with the kernel.
+/// Dropping it un-allows the buffer: the kernel hands back the (pointer,
+/// length) of the memory it was given, which is checked against the
+/// original buffer to confirm the kernel is relinquishing the same memory
+/// it was sharing.
+pub struct SharedMemory<'a> {
+    driver_number: usize,
+    allow_number: usize,
+    buffer: &'a mut [u8],
+}
+
+impl<'a> SharedMemory<'a> {
+    pub(crate) fn new(
+        driver_number: usize,
+        allow_number: usize,
+        buffer: &'a mut [u8],
+    ) -> SharedMemory<'a> {
+        SharedMemory {
+            driver_number,
+            allow_number,
+            buffer,
+        }
+    }
+
+    pub fn as_mut(&mut self) -> &mut [u8] {
+        self.buffer
+    }
+
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+}
+
+impl<'a> Drop for SharedMemory<'a> {
+    fn drop(&mut self) {
+        let (r0, r1, r2, r3) = unsafe {
+            syscalls::raw::allow_readwrite(
+                self.driver_number,
+                self.allow_number,
+                core::ptr::null_mut(),
+                0,
+            )
+        };
+        if let Ok(SuccessValues::U32U32(ptr, len)) = SyscallReturn::decode(r0, r1, r2, r3).into_result() {
+            // The kernel should hand back exactly the buffer we originally
+            // shared; check that before trusting the swap was atomic. This is
+            // a real invariant we rely on, not a debug-only sanity check, so
+            // it must not be compiled out in release builds.
+            assert_eq!(ptr as *mut u8, self.buffer.as_mut_ptr());
+            assert_eq!(len as usize, self.buffer.len());
+        }
+    }
+}
+
+/// A token representing a read-only buffer currently shared with the kernel.
+/// Dropping it un-allows the buffer, mirroring `SharedMemory`.
+pub struct ReadOnlySharedMemory<'a> {
+    driver_number: usize,
+    allow_number: usize,
+    buffer: &'a [u8],
+}
+
+impl<'a> ReadOnlySharedMemory<'a> {
+    pub(crate) fn new(
+        driver_number: usize,
+        allow_number: usize,
+        buffer: &'a [u8],
+    ) -> ReadOnlySharedMemory<'a> {
+        ReadOnlySharedMemory {
+            driver_number,
+            allow_number,
+            buffer,
+        }
+    }
+
+    pub fn as_ref(&self) -> &[u8] {
+        self.buffer
+    }
+
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+}
+
+impl<'a> Drop for ReadOnlySharedMemory<'a> {
+    fn drop(&mut self) {
+        let (r0, r1, r2, r3) = unsafe {
+            syscalls::raw::allow_readonly(
+                self.driver_number,
+                self.allow_number,
+                core::ptr::null(),
+                0,
+            )
+        };
+        if let Ok(SuccessValues::U32U32(ptr, len)) = SyscallReturn::decode(r0, r1, r2, r3).into_result() {
+            assert_eq!(ptr as *const u8, self.buffer.as_ptr());
+            assert_eq!(len as usize, self.buffer.len());
+        }
+    }
+}
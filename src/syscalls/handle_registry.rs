@@ -0,0 +1,161 @@
+//! Generational handle registry for `subscribe`'s userdata.
+//!
+//! The kernel echoes `subscribe`'s userdata back verbatim on every upcall.
+//! Passing a raw `*mut CB` through that channel is unsound: if the
+//! `CallbackSubscription` outlives the callback object (or is simply
+//! dropped and its slot reused), the kernel would deliver an upcall into
+//! freed or repurposed memory. Instead we hand the kernel an opaque handle
+//! — a slot index packed with a generation counter — and look the real
+//! pointer up ourselves, silently ignoring upcalls for slots that have
+//! since been freed or reused.
+
+use super::ErrorCode;
+
+const MAX_HANDLES: usize = 32;
+
+#[derive(Copy, Clone)]
+struct Slot {
+    generation: u16,
+    callback_ptr: usize,
+    occupied: bool,
+}
+
+impl Slot {
+    const EMPTY: Slot = Slot {
+        generation: 0,
+        callback_ptr: 0,
+        occupied: false,
+    };
+}
+
+static mut SLOTS: [Slot; MAX_HANDLES] = [Slot::EMPTY; MAX_HANDLES];
+
+/// Allocates a slot for `callback_ptr`, returning the packed handle to hand
+/// the kernel as `subscribe`'s userdata. Fails with `ErrorCode::NoMem` if
+/// every slot is currently live.
+///
+/// # Safety
+///
+/// Must only be called from the single application thread that also
+/// services upcalls; there is no synchronization against concurrent access.
+pub(crate) unsafe fn allocate(callback_ptr: usize) -> Result<usize, ErrorCode> {
+    for (index, slot) in SLOTS.iter_mut().enumerate() {
+        if !slot.occupied {
+            slot.occupied = true;
+            slot.callback_ptr = callback_ptr;
+            return Ok(pack(index, slot.generation));
+        }
+    }
+    Err(ErrorCode::NoMem)
+}
+
+/// Frees the slot `handle` refers to and bumps its generation, so any
+/// upcall still in flight for the old generation is recognized as stale.
+///
+/// # Safety
+///
+/// See `allocate`.
+pub(crate) unsafe fn free(handle: usize) {
+    if let Some(slot) = SLOTS.get_mut(unpack_index(handle)) {
+        slot.occupied = false;
+        slot.generation = slot.generation.wrapping_add(1);
+    }
+}
+
+/// Looks `handle` up, returning the live callback pointer, or `None` if the
+/// slot is stale or free.
+///
+/// # Safety
+///
+/// See `allocate`.
+pub(crate) unsafe fn lookup(handle: usize) -> Option<usize> {
+    let generation = unpack_generation(handle);
+    let slot = SLOTS.get(unpack_index(handle))?;
+    if slot.occupied && slot.generation == generation {
+        Some(slot.callback_ptr)
+    } else {
+        None
+    }
+}
+
+fn pack(index: usize, generation: u16) -> usize {
+    (index << 16) | generation as usize
+}
+
+fn unpack_index(handle: usize) -> usize {
+    handle >> 16
+}
+
+fn unpack_generation(handle: usize) -> u16 {
+    (handle & 0xffff) as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // `SLOTS` is a single global registry, so tests that touch it must not
+    // run concurrently with each other.
+    static TEST_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    fn reset() {
+        unsafe {
+            for slot in SLOTS.iter_mut() {
+                *slot = Slot::EMPTY;
+            }
+        }
+    }
+
+    #[test]
+    fn allocate_lookup_free_round_trip() {
+        let _guard = TEST_LOCK.lock().unwrap();
+        reset();
+        unsafe {
+            let handle = allocate(0x1234).unwrap();
+            assert_eq!(lookup(handle), Some(0x1234));
+            free(handle);
+            assert_eq!(lookup(handle), None);
+        }
+    }
+
+    #[test]
+    fn stale_handle_is_rejected_after_the_slot_is_reused() {
+        let _guard = TEST_LOCK.lock().unwrap();
+        reset();
+        unsafe {
+            let first = allocate(0x1).unwrap();
+            free(first);
+            let second = allocate(0x2).unwrap();
+            // Same slot index, but a bumped generation: the old handle must
+            // not resolve to the new occupant.
+            assert_eq!(lookup(first), None);
+            assert_eq!(lookup(second), Some(0x2));
+        }
+    }
+
+    #[test]
+    fn allocate_fails_with_no_mem_once_the_registry_is_full() {
+        let _guard = TEST_LOCK.lock().unwrap();
+        reset();
+        unsafe {
+            for i in 0..MAX_HANDLES {
+                allocate(i).unwrap();
+            }
+            assert_eq!(allocate(0xffff), Err(ErrorCode::NoMem));
+        }
+    }
+
+    #[test]
+    fn freeing_a_slot_makes_room_for_a_new_allocation() {
+        let _guard = TEST_LOCK.lock().unwrap();
+        reset();
+        unsafe {
+            let handles: std::vec::Vec<usize> =
+                (0..MAX_HANDLES).map(|i| allocate(i).unwrap()).collect();
+            assert_eq!(allocate(0xffff), Err(ErrorCode::NoMem));
+
+            free(handles[0]);
+            assert!(allocate(0xffff).is_ok());
+        }
+    }
+}
@@ -5,10 +5,13 @@
     path = "platform_mock.rs"
 )]
 mod platform;
+mod raw_syscall_return;
+pub(crate) mod handle_registry;
 
 use crate::callback::CallbackSubscription;
 use crate::callback::SubscribableCallback;
-use crate::shared_memory::SharedMemory;
+use crate::shared_memory::{ReadOnlySharedMemory, SharedMemory};
+pub use raw_syscall_return::{ErrorCode, SuccessValues, SyscallReturn};
 
 pub mod raw {
     use super::platform;
@@ -30,35 +33,47 @@ pub mod raw {
     }
 }
 
+/// Blocks until at least one upcall has been serviced, as `raw::yieldk` did
+/// under the Tock 1.0 ABI. Prefer this over `raw::yieldk` in new code; it
+/// goes through the Tock 2.0 two-argument yield instead of the
+/// `async-support` back-channel.
+pub fn yield_wait() {
+    unsafe { raw::yield2(0, 0) }
+}
+
+/// Services at most one upcall without blocking, returning whether an
+/// upcall was actually serviced. Lets an app poll for events in its own busy
+/// loop instead of parking the core.
+pub fn yield_no_wait() -> bool {
+    let mut upcall_serviced: u8 = 0;
+    unsafe { raw::yield2(1, &mut upcall_serviced as *mut u8 as usize) };
+    upcall_serviced != 0
+}
+
 pub fn subscribe<CB: SubscribableCallback>(
     driver_number: usize,
     subscribe_number: usize,
     callback: &mut CB,
-) -> Result<CallbackSubscription, isize> {
+) -> Result<CallbackSubscription, ErrorCode> {
     extern "C" fn c_callback<CB: SubscribableCallback>(
         arg1: usize,
         arg2: usize,
         arg3: usize,
-        data: usize,
+        handle: usize,
     ) {
-        let callback = unsafe { &mut *(data as *mut CB) };
-        callback.call_rust(arg1, arg2, arg3);
+        if let Some(callback_ptr) = unsafe { handle_registry::lookup(handle) } {
+            let callback = unsafe { &mut *(callback_ptr as *mut CB) };
+            callback.call_rust(arg1, arg2, arg3);
+        }
     }
 
-    let return_code = {
-        subscribe_fn(
-            driver_number,
-            subscribe_number,
-            c_callback::<CB>,
-            callback as *mut CB as usize,
-        )
-    };
-
-    if return_code == 0 {
-        Ok(CallbackSubscription::new(driver_number, subscribe_number))
-    } else {
-        Err(return_code)
-    }
+    let handle = unsafe { handle_registry::allocate(callback as *mut CB as usize) }?;
+    subscribe_fn(driver_number, subscribe_number, c_callback::<CB>, handle)
+        .map(|_| CallbackSubscription::new(driver_number, subscribe_number, handle))
+        .map_err(|e| {
+            unsafe { handle_registry::free(handle) };
+            e
+        })
 }
 
 pub fn subscribe_fn(
@@ -66,19 +81,27 @@ pub fn subscribe_fn(
     subscribe_number: usize,
     callback: extern "C" fn(usize, usize, usize, usize),
     userdata: usize,
-) -> isize {
-    unsafe {
+) -> Result<SuccessValues, ErrorCode> {
+    let (r0, r1, r2, r3) = unsafe {
         raw::subscribe(
             driver_number,
             subscribe_number,
             callback as *const _,
             userdata,
         )
-    }
+    };
+    SyscallReturn::decode(r0, r1, r2, r3).into_result()
 }
 
-pub fn command(driver_number: usize, command_number: usize, arg1: usize, arg2: usize) -> isize {
-    unsafe { raw::command(driver_number, command_number, arg1, arg2) }
+pub fn command(
+    driver_number: usize,
+    command_number: usize,
+    arg1: usize,
+    arg2: usize,
+) -> Result<SuccessValues, ErrorCode> {
+    let (r0, r1, r2, r3) =
+        unsafe { raw::command(driver_number, command_number, arg1, arg2) };
+    SyscallReturn::decode(r0, r1, r2, r3).into_result()
 }
 
 // command1_insecure, is a variant of command() that only sets the first
@@ -96,27 +119,104 @@ pub fn command1_insecure(driver_number: usize, command_number: usize, arg: usize
     unsafe { raw::command1(driver_number, command_number, arg) }
 }
 
-pub fn allow(
+pub fn allow_readwrite(
     driver_number: usize,
     allow_number: usize,
     buffer_to_share: &mut [u8],
-) -> Result<SharedMemory, isize> {
+) -> Result<SharedMemory, ErrorCode> {
     let len = buffer_to_share.len();
-    let return_code = unsafe {
-        raw::allow(
+    let (r0, r1, r2, r3) = unsafe {
+        raw::allow_readwrite(
             driver_number,
             allow_number,
             buffer_to_share.as_mut_ptr(),
             len,
         )
     };
-    if return_code == 0 {
-        Ok(SharedMemory::new(
+    SyscallReturn::decode(r0, r1, r2, r3)
+        .into_result()
+        .map(move |_| SharedMemory::new(driver_number, allow_number, buffer_to_share))
+}
+
+pub fn allow_readonly(
+    driver_number: usize,
+    allow_number: usize,
+    buffer_to_share: &[u8],
+) -> Result<ReadOnlySharedMemory, ErrorCode> {
+    let len = buffer_to_share.len();
+    let (r0, r1, r2, r3) = unsafe {
+        raw::allow_readonly(
             driver_number,
             allow_number,
-            buffer_to_share,
-        ))
-    } else {
-        Err(return_code)
+            buffer_to_share.as_ptr(),
+            len,
+        )
+    };
+    SyscallReturn::decode(r0, r1, r2, r3)
+        .into_result()
+        .map(move |_| ReadOnlySharedMemory::new(driver_number, allow_number, buffer_to_share))
+}
+
+/// Operation numbers for the `memop` syscall, as defined by the Tock kernel.
+#[derive(Copy, Clone)]
+enum MemopOperation {
+    Brk = 0,
+    Sbrk = 1,
+    MemoryStart = 2,
+    MemoryEnd = 3,
+    FlashStart = 4,
+    FlashEnd = 5,
+    GrantRegionBegin = 6,
+}
+
+/// Issues a `memop` syscall, decoding the kernel's structured reply.
+pub fn memop(operation_type: usize, arg1: usize) -> Result<SuccessValues, ErrorCode> {
+    let (r0, r1, r2, r3) = unsafe { raw::memop(operation_type, arg1) };
+    SyscallReturn::decode(r0, r1, r2, r3).into_result()
+}
+
+fn memop_ptr(operation: MemopOperation, arg1: usize) -> Result<*const u8, ErrorCode> {
+    match memop(operation as usize, arg1)? {
+        SuccessValues::U32(ptr) => Ok(ptr as *const u8),
+        // A conformant kernel always replies to these memops with
+        // `SuccessU32`; anything else is a malformed reply, not a genuine
+        // null address.
+        _ => Err(ErrorCode::Fail),
     }
 }
+
+/// Sets the app's break (the end of the heap) to `new_break`.
+pub fn brk(new_break: *const u8) -> Result<(), ErrorCode> {
+    memop(MemopOperation::Brk as usize, new_break as usize).map(|_| ())
+}
+
+/// Increments the app's break by `increment` bytes, returning the previous
+/// break.
+pub fn sbrk(increment: isize) -> Result<*const u8, ErrorCode> {
+    memop_ptr(MemopOperation::Sbrk, increment as usize)
+}
+
+/// Returns the lowest address of the app's RAM region.
+pub fn memory_start() -> Result<*const u8, ErrorCode> {
+    memop_ptr(MemopOperation::MemoryStart, 0)
+}
+
+/// Returns the highest address of the app's RAM region.
+pub fn memory_end() -> Result<*const u8, ErrorCode> {
+    memop_ptr(MemopOperation::MemoryEnd, 0)
+}
+
+/// Returns the lowest address of the app's flash region.
+pub fn flash_start() -> Result<*const u8, ErrorCode> {
+    memop_ptr(MemopOperation::FlashStart, 0)
+}
+
+/// Returns the highest address of the app's flash region.
+pub fn flash_end() -> Result<*const u8, ErrorCode> {
+    memop_ptr(MemopOperation::FlashEnd, 0)
+}
+
+/// Returns the start address of the app's grant region.
+pub fn grant_region_begin() -> Result<*const u8, ErrorCode> {
+    memop_ptr(MemopOperation::GrantRegionBegin, 0)
+}
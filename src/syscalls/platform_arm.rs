@@ -0,0 +1,123 @@
+//! Raw ARM (Cortex-M) syscall trampolines.
+//!
+//! Each syscall class is selected by the `svc` instruction's immediate:
+//! 0 = yield, 1 = subscribe, 2 = command, 3 = allow_readwrite, 4 = memop,
+//! 6 = allow_readonly. Arguments and the kernel's structured return value
+//! both travel in `r0..r3`.
+
+use core::arch::asm;
+
+pub unsafe fn yieldk() {
+    asm!("svc 0", options(nomem, nostack, preserves_flags));
+}
+
+/// The Tock 2.0 two-argument yield. `which` selects yield-wait (0) or
+/// yield-no-wait (1); for yield-no-wait, `param` is a pointer to a byte the
+/// kernel sets to 1 if it serviced an upcall.
+pub unsafe fn yield2(which: usize, param: usize) {
+    asm!(
+        "svc 0",
+        in("r0") which,
+        in("r1") param,
+        options(nostack, preserves_flags),
+    );
+}
+
+pub unsafe fn subscribe(
+    driver_number: usize,
+    subscribe_number: usize,
+    callback: *const unsafe extern "C" fn(usize, usize, usize, usize),
+    userdata: usize,
+) -> (usize, usize, usize, usize) {
+    let (r0, r1, r2, r3);
+    asm!(
+        "svc 1",
+        inlateout("r0") driver_number => r0,
+        inlateout("r1") subscribe_number => r1,
+        inlateout("r2") callback as usize => r2,
+        inlateout("r3") userdata => r3,
+        options(nostack),
+    );
+    (r0, r1, r2, r3)
+}
+
+pub unsafe fn command(
+    driver_number: usize,
+    command_number: usize,
+    arg1: usize,
+    arg2: usize,
+) -> (usize, usize, usize, usize) {
+    let (r0, r1, r2, r3);
+    asm!(
+        "svc 2",
+        inlateout("r0") driver_number => r0,
+        inlateout("r1") command_number => r1,
+        inlateout("r2") arg1 => r2,
+        inlateout("r3") arg2 => r3,
+        options(nostack),
+    );
+    (r0, r1, r2, r3)
+}
+
+pub unsafe fn command1(driver_number: usize, command_number: usize, arg: usize) -> isize {
+    let (r0, r1, r2, r3);
+    asm!(
+        "svc 2",
+        inlateout("r0") driver_number => r0,
+        inlateout("r1") command_number => r1,
+        inlateout("r2") arg => r2,
+        lateout("r3") r3,
+        options(nostack),
+    );
+    let _ = (r1, r2, r3);
+    r0 as isize
+}
+
+pub unsafe fn allow_readwrite(
+    driver_number: usize,
+    allow_number: usize,
+    pointer: *mut u8,
+    size: usize,
+) -> (usize, usize, usize, usize) {
+    let (r0, r1, r2, r3);
+    asm!(
+        "svc 3",
+        inlateout("r0") driver_number => r0,
+        inlateout("r1") allow_number => r1,
+        inlateout("r2") pointer as usize => r2,
+        inlateout("r3") size => r3,
+        options(nostack),
+    );
+    (r0, r1, r2, r3)
+}
+
+pub unsafe fn memop(operation_type: usize, arg1: usize) -> (usize, usize, usize, usize) {
+    let (r0, r1, r2, r3);
+    asm!(
+        "svc 4",
+        inlateout("r0") operation_type => r0,
+        inlateout("r1") arg1 => r1,
+        lateout("r2") r2,
+        lateout("r3") r3,
+        options(nostack),
+    );
+    (r0, r1, r2, r3)
+}
+
+pub unsafe fn allow_readonly(
+    driver_number: usize,
+    allow_number: usize,
+    pointer: *const u8,
+    size: usize,
+) -> (usize, usize, usize, usize) {
+    let (r0, r1, r2, r3);
+    asm!(
+        "svc 6",
+        inlateout("r0") driver_number => r0,
+        inlateout("r1") allow_number => r1,
+        inlateout("r2") pointer as usize => r2,
+        inlateout("r3") size => r3,
+        options(nostack),
+    );
+    (r0, r1, r2, r3)
+}
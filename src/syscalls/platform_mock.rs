@@ -0,0 +1,55 @@
+//! Stand-in platform used when building for a target that isn't ARM or
+//! RISC-V (e.g. running `cargo check`/`cargo doc` on the host). There is no
+//! Tock kernel to trap into, so every entry point just panics.
+
+pub unsafe fn yieldk() {
+    unimplemented!("yieldk is not supported on this platform");
+}
+
+pub unsafe fn yield2(_which: usize, _param: usize) {
+    unimplemented!("yield2 is not supported on this platform");
+}
+
+pub unsafe fn subscribe(
+    _driver_number: usize,
+    _subscribe_number: usize,
+    _callback: *const unsafe extern "C" fn(usize, usize, usize, usize),
+    _userdata: usize,
+) -> (usize, usize, usize, usize) {
+    unimplemented!("subscribe is not supported on this platform");
+}
+
+pub unsafe fn command(
+    _driver_number: usize,
+    _command_number: usize,
+    _arg1: usize,
+    _arg2: usize,
+) -> (usize, usize, usize, usize) {
+    unimplemented!("command is not supported on this platform");
+}
+
+pub unsafe fn command1(_driver_number: usize, _command_number: usize, _arg: usize) -> isize {
+    unimplemented!("command1 is not supported on this platform");
+}
+
+pub unsafe fn memop(_operation_type: usize, _arg1: usize) -> (usize, usize, usize, usize) {
+    unimplemented!("memop is not supported on this platform");
+}
+
+pub unsafe fn allow_readwrite(
+    _driver_number: usize,
+    _allow_number: usize,
+    _pointer: *mut u8,
+    _size: usize,
+) -> (usize, usize, usize, usize) {
+    unimplemented!("allow_readwrite is not supported on this platform");
+}
+
+pub unsafe fn allow_readonly(
+    _driver_number: usize,
+    _allow_number: usize,
+    _pointer: *const u8,
+    _size: usize,
+) -> (usize, usize, usize, usize) {
+    unimplemented!("allow_readonly is not supported on this platform");
+}
@@ -0,0 +1,134 @@
+//! Raw RISC-V syscall trampolines.
+//!
+//! Arguments and the kernel's structured return value travel in `a0..a3`;
+//! `a4` selects the syscall class (0 = yield, 1 = subscribe, 2 = command,
+//! 3 = allow_readwrite, 4 = memop, 6 = allow_readonly), and the trap is
+//! taken with `ecall`.
+
+use core::arch::asm;
+
+pub unsafe fn yieldk() {
+    asm!(
+        "ecall",
+        in("a4") 0usize,
+        options(nomem, nostack, preserves_flags),
+    );
+}
+
+/// The Tock 2.0 two-argument yield. `which` selects yield-wait (0) or
+/// yield-no-wait (1); for yield-no-wait, `param` is a pointer to a byte the
+/// kernel sets to 1 if it serviced an upcall.
+pub unsafe fn yield2(which: usize, param: usize) {
+    asm!(
+        "ecall",
+        in("a0") which,
+        in("a1") param,
+        in("a4") 0usize,
+        options(nostack, preserves_flags),
+    );
+}
+
+pub unsafe fn subscribe(
+    driver_number: usize,
+    subscribe_number: usize,
+    callback: *const unsafe extern "C" fn(usize, usize, usize, usize),
+    userdata: usize,
+) -> (usize, usize, usize, usize) {
+    let (a0, a1, a2, a3);
+    asm!(
+        "ecall",
+        inlateout("a0") driver_number => a0,
+        inlateout("a1") subscribe_number => a1,
+        inlateout("a2") callback as usize => a2,
+        inlateout("a3") userdata => a3,
+        in("a4") 1usize,
+        options(nostack),
+    );
+    (a0, a1, a2, a3)
+}
+
+pub unsafe fn command(
+    driver_number: usize,
+    command_number: usize,
+    arg1: usize,
+    arg2: usize,
+) -> (usize, usize, usize, usize) {
+    let (a0, a1, a2, a3);
+    asm!(
+        "ecall",
+        inlateout("a0") driver_number => a0,
+        inlateout("a1") command_number => a1,
+        inlateout("a2") arg1 => a2,
+        inlateout("a3") arg2 => a3,
+        in("a4") 2usize,
+        options(nostack),
+    );
+    (a0, a1, a2, a3)
+}
+
+pub unsafe fn command1(driver_number: usize, command_number: usize, arg: usize) -> isize {
+    let (a0, a1, a2, a3);
+    asm!(
+        "ecall",
+        inlateout("a0") driver_number => a0,
+        inlateout("a1") command_number => a1,
+        inlateout("a2") arg => a2,
+        lateout("a3") a3,
+        in("a4") 2usize,
+        options(nostack),
+    );
+    let _ = (a1, a2, a3);
+    a0 as isize
+}
+
+pub unsafe fn allow_readwrite(
+    driver_number: usize,
+    allow_number: usize,
+    pointer: *mut u8,
+    size: usize,
+) -> (usize, usize, usize, usize) {
+    let (a0, a1, a2, a3);
+    asm!(
+        "ecall",
+        inlateout("a0") driver_number => a0,
+        inlateout("a1") allow_number => a1,
+        inlateout("a2") pointer as usize => a2,
+        inlateout("a3") size => a3,
+        in("a4") 3usize,
+        options(nostack),
+    );
+    (a0, a1, a2, a3)
+}
+
+pub unsafe fn memop(operation_type: usize, arg1: usize) -> (usize, usize, usize, usize) {
+    let (a0, a1, a2, a3);
+    asm!(
+        "ecall",
+        inlateout("a0") operation_type => a0,
+        inlateout("a1") arg1 => a1,
+        lateout("a2") a2,
+        lateout("a3") a3,
+        in("a4") 4usize,
+        options(nostack),
+    );
+    (a0, a1, a2, a3)
+}
+
+pub unsafe fn allow_readonly(
+    driver_number: usize,
+    allow_number: usize,
+    pointer: *const u8,
+    size: usize,
+) -> (usize, usize, usize, usize) {
+    let (a0, a1, a2, a3);
+    asm!(
+        "ecall",
+        inlateout("a0") driver_number => a0,
+        inlateout("a1") allow_number => a1,
+        inlateout("a2") pointer as usize => a2,
+        inlateout("a3") size => a3,
+        in("a4") 6usize,
+        options(nostack),
+    );
+    (a0, a1, a2, a3)
+}
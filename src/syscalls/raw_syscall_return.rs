@@ -0,0 +1,198 @@
+//! Decoding for the Tock 2.0 structured syscall return-value ABI.
+//!
+//! The kernel no longer replies to `command`/`subscribe`/`allow` with a
+//! single `isize` return code. Instead it leaves up to four register-sized
+//! words behind (`r0..r3` on ARM, `a0..a3` on RISC-V): the first word is a
+//! *return variant* tag, and the rest carry whatever payload that variant
+//! defines.
+
+/// A Tock kernel error code, returned in register 1 on any failure variant.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(usize)]
+pub enum ErrorCode {
+    Fail = 1,
+    Busy = 2,
+    Already = 3,
+    Off = 4,
+    Reserve = 5,
+    Invalid = 6,
+    Size = 7,
+    Cancel = 8,
+    NoMem = 9,
+    NoSupport = 10,
+    NoDevice = 11,
+    UnInstalled = 12,
+    NoAck = 13,
+}
+
+impl ErrorCode {
+    fn from_usize(value: usize) -> ErrorCode {
+        match value {
+            2 => ErrorCode::Busy,
+            3 => ErrorCode::Already,
+            4 => ErrorCode::Off,
+            5 => ErrorCode::Reserve,
+            6 => ErrorCode::Invalid,
+            7 => ErrorCode::Size,
+            8 => ErrorCode::Cancel,
+            9 => ErrorCode::NoMem,
+            10 => ErrorCode::NoSupport,
+            11 => ErrorCode::NoDevice,
+            12 => ErrorCode::UnInstalled,
+            13 => ErrorCode::NoAck,
+            // An unrecognized variant shouldn't happen with a conformant
+            // kernel; fall back to the generic failure code rather than
+            // panicking on a malformed reply.
+            _ => ErrorCode::Fail,
+        }
+    }
+}
+
+/// The values a successful `command`/`subscribe`/`allow` call can carry.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SuccessValues {
+    None,
+    U32(u32),
+    U32U32(u32, u32),
+    U64(u64),
+    U32U32U32(u32, u32, u32),
+}
+
+/// The fully-decoded form of the kernel's structured return value, as laid
+/// out in the Tock 2.0 syscall ABI.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SyscallReturn {
+    Failure(ErrorCode),
+    FailureU32(ErrorCode, u32),
+    FailureU32U32(ErrorCode, u32, u32),
+    FailureU64(ErrorCode, u64),
+    Success,
+    SuccessU32(u32),
+    SuccessU32U32(u32, u32),
+    SuccessU64(u64),
+    SuccessU32U32U32(u32, u32, u32),
+}
+
+impl SyscallReturn {
+    /// Decodes the raw `(r0, r1, r2, r3)` register tuple a `command`,
+    /// `subscribe`, or `allow` trap left behind.
+    pub fn decode(r0: usize, r1: usize, r2: usize, r3: usize) -> SyscallReturn {
+        match r0 {
+            0 => SyscallReturn::Failure(ErrorCode::from_usize(r1)),
+            1 => SyscallReturn::FailureU32(ErrorCode::from_usize(r1), r2 as u32),
+            2 => SyscallReturn::FailureU32U32(ErrorCode::from_usize(r1), r2 as u32, r3 as u32),
+            3 => SyscallReturn::FailureU64(
+                ErrorCode::from_usize(r1),
+                (r2 as u64) | ((r3 as u64) << 32),
+            ),
+            128 => SyscallReturn::Success,
+            129 => SyscallReturn::SuccessU32(r1 as u32),
+            130 => SyscallReturn::SuccessU32U32(r1 as u32, r2 as u32),
+            131 => SyscallReturn::SuccessU64((r1 as u64) | ((r2 as u64) << 32)),
+            132 => SyscallReturn::SuccessU32U32U32(r1 as u32, r2 as u32, r3 as u32),
+            // A conformant kernel will never send an unknown variant; treat
+            // it the same as the generic failure case.
+            _ => SyscallReturn::Failure(ErrorCode::Fail),
+        }
+    }
+
+    /// Collapses the full variant detail down to the `Result` shape callers
+    /// actually want: the success payload, or just the error code (the extra
+    /// failure payload words aren't meaningful for any current driver).
+    pub fn into_result(self) -> Result<SuccessValues, ErrorCode> {
+        match self {
+            SyscallReturn::Failure(e) => Err(e),
+            SyscallReturn::FailureU32(e, _) => Err(e),
+            SyscallReturn::FailureU32U32(e, _, _) => Err(e),
+            SyscallReturn::FailureU64(e, _) => Err(e),
+            SyscallReturn::Success => Ok(SuccessValues::None),
+            SyscallReturn::SuccessU32(v) => Ok(SuccessValues::U32(v)),
+            SyscallReturn::SuccessU32U32(a, b) => Ok(SuccessValues::U32U32(a, b)),
+            SyscallReturn::SuccessU64(v) => Ok(SuccessValues::U64(v)),
+            SyscallReturn::SuccessU32U32U32(a, b, c) => Ok(SuccessValues::U32U32U32(a, b, c)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_failure_variants() {
+        assert_eq!(
+            SyscallReturn::decode(0, 2, 0, 0),
+            SyscallReturn::Failure(ErrorCode::Busy)
+        );
+        assert_eq!(
+            SyscallReturn::decode(1, 9, 42, 0),
+            SyscallReturn::FailureU32(ErrorCode::NoMem, 42)
+        );
+        assert_eq!(
+            SyscallReturn::decode(2, 6, 1, 2),
+            SyscallReturn::FailureU32U32(ErrorCode::Invalid, 1, 2)
+        );
+        assert_eq!(
+            SyscallReturn::decode(3, 1, 1, 1),
+            SyscallReturn::FailureU64(ErrorCode::Fail, (1u64) | (1u64 << 32))
+        );
+    }
+
+    #[test]
+    fn decodes_success_variants() {
+        assert_eq!(SyscallReturn::decode(128, 0, 0, 0), SyscallReturn::Success);
+        assert_eq!(
+            SyscallReturn::decode(129, 7, 0, 0),
+            SyscallReturn::SuccessU32(7)
+        );
+        assert_eq!(
+            SyscallReturn::decode(130, 1, 2, 0),
+            SyscallReturn::SuccessU32U32(1, 2)
+        );
+        assert_eq!(
+            SyscallReturn::decode(131, 1, 1, 0),
+            SyscallReturn::SuccessU64((1u64) | (1u64 << 32))
+        );
+        assert_eq!(
+            SyscallReturn::decode(132, 1, 2, 3),
+            SyscallReturn::SuccessU32U32U32(1, 2, 3)
+        );
+    }
+
+    #[test]
+    fn decodes_unknown_variant_as_failure() {
+        assert_eq!(
+            SyscallReturn::decode(255, 0, 0, 0),
+            SyscallReturn::Failure(ErrorCode::Fail)
+        );
+    }
+
+    #[test]
+    fn unknown_error_code_falls_back_to_fail() {
+        assert_eq!(
+            SyscallReturn::decode(0, 255, 0, 0),
+            SyscallReturn::Failure(ErrorCode::Fail)
+        );
+    }
+
+    #[test]
+    fn into_result_collapses_failures_to_the_error_code() {
+        assert_eq!(
+            SyscallReturn::FailureU32U32(ErrorCode::Busy, 1, 2).into_result(),
+            Err(ErrorCode::Busy)
+        );
+    }
+
+    #[test]
+    fn into_result_collapses_successes_to_their_payload() {
+        assert_eq!(SyscallReturn::Success.into_result(), Ok(SuccessValues::None));
+        assert_eq!(
+            SyscallReturn::SuccessU32(9).into_result(),
+            Ok(SuccessValues::U32(9))
+        );
+        assert_eq!(
+            SyscallReturn::SuccessU32U32U32(1, 2, 3).into_result(),
+            Ok(SuccessValues::U32U32U32(1, 2, 3))
+        );
+    }
+}